@@ -0,0 +1,274 @@
+use geometry::Prim;
+use raytracer::Ray;
+use vec3::Vec3;
+
+/// An axis-aligned bounding box, used both as a BVH node's bound and as the
+/// unit of overlap test during the slab traversal.
+///
+/// Building a `BVH` relies on `Prim::aabb()` to bound each primitive; see
+/// `geometry::Prim`.
+#[derive(Clone, Copy)]
+pub struct AABB {
+    pub min: Vec3,
+    pub max: Vec3
+}
+
+impl AABB {
+    pub fn empty() -> AABB {
+        AABB {
+            min: Vec3 { x: ::std::f64::INFINITY, y: ::std::f64::INFINITY, z: ::std::f64::INFINITY },
+            max: Vec3 { x: ::std::f64::NEG_INFINITY, y: ::std::f64::NEG_INFINITY, z: ::std::f64::NEG_INFINITY }
+        }
+    }
+
+    pub fn from_point(p: Vec3) -> AABB {
+        AABB { min: p, max: p }
+    }
+
+    pub fn extend(&mut self, p: Vec3) {
+        self.min = Vec3 { x: self.min.x.min(p.x), y: self.min.y.min(p.y), z: self.min.z.min(p.z) };
+        self.max = Vec3 { x: self.max.x.max(p.x), y: self.max.y.max(p.y), z: self.max.z.max(p.z) };
+    }
+
+    pub fn union(&self, other: &AABB) -> AABB {
+        let mut result = *self;
+        result.extend(other.min);
+        result.extend(other.max);
+        result
+    }
+
+    pub fn centroid(&self) -> Vec3 {
+        (self.min + self.max).scale(0.5)
+    }
+
+    /// Surface area, used by the SAH cost estimate during the build.
+    pub fn area(&self) -> f64 {
+        let d = self.max - self.min;
+        if d.x < 0.0 || d.y < 0.0 || d.z < 0.0 { return 0.0 }
+        2.0 * (d.x * d.y + d.y * d.z + d.z * d.x)
+    }
+
+    /// Slab test. Returns the entry distance along `ray` if it's within
+    /// `[0, max_t]`, or `None` if the ray misses the box entirely.
+    pub fn intersects(&self, ray: &Ray, max_t: f64) -> Option<f64> {
+        let mut t_min = 0.0f64;
+        let mut t_max = max_t;
+
+        for axis in 0..3 {
+            let (origin, dir, lo, hi) = match axis {
+                0 => (ray.origin.x, ray.direction.x, self.min.x, self.max.x),
+                1 => (ray.origin.y, ray.direction.y, self.min.y, self.max.y),
+                _ => (ray.origin.z, ray.direction.z, self.min.z, self.max.z)
+            };
+
+            if dir.abs() < 1e-12 {
+                if origin < lo || origin > hi { return None }
+                continue;
+            }
+
+            let inv_dir = 1.0 / dir;
+            let mut t0 = (lo - origin) * inv_dir;
+            let mut t1 = (hi - origin) * inv_dir;
+            if t0 > t1 { let tmp = t0; t0 = t1; t1 = tmp; }
+
+            t_min = t_min.max(t0);
+            t_max = t_max.min(t1);
+            if t_min > t_max { return None }
+        }
+
+        Some(t_min)
+    }
+}
+
+fn axis_of(v: Vec3, axis: usize) -> f64 {
+    match axis {
+        0 => v.x,
+        1 => v.y,
+        _ => v.z
+    }
+}
+
+/// A bounding-volume hierarchy over a list of primitives (in practice, a
+/// `Mesh`'s triangles). Leaves hold indices back into the slice they were
+/// built from rather than owning copies.
+pub enum BVH {
+    Node(Box<BVH>, Box<BVH>, AABB),
+    Leaf(AABB, Vec<usize>)
+}
+
+const MAX_LEAF_PRIMS: usize = 4;
+const SAH_BUCKETS: usize = 12;
+
+impl BVH {
+    pub fn build(prims: &[Box<Prim+Send+Sync>]) -> BVH {
+        let mut indices: Vec<usize> = (0..prims.len()).collect();
+        Self::build_range(prims, &mut indices)
+    }
+
+    fn build_range(prims: &[Box<Prim+Send+Sync>], indices: &mut [usize]) -> BVH {
+        let bounds = Self::bounds_of(prims, indices);
+
+        if indices.len() <= MAX_LEAF_PRIMS {
+            return BVH::Leaf(bounds, indices.to_vec());
+        }
+
+        let mut centroid_bounds = AABB::empty();
+        for &i in indices.iter() {
+            centroid_bounds.extend(prims[i].aabb().centroid());
+        }
+
+        let extent = centroid_bounds.max - centroid_bounds.min;
+        let axis = if extent.x > extent.y && extent.x > extent.z { 0 }
+                   else if extent.y > extent.z { 1 }
+                   else { 2 };
+
+        if axis_of(extent, axis) < 1e-12 {
+            return BVH::Leaf(bounds, indices.to_vec());
+        }
+
+        indices.sort_by(|&a, &b| {
+            let ca = axis_of(prims[a].aabb().centroid(), axis);
+            let cb = axis_of(prims[b].aabb().centroid(), axis);
+            ca.partial_cmp(&cb).unwrap()
+        });
+
+        let split = Self::sah_split(prims, indices, axis)
+            .unwrap_or(indices.len() / 2)
+            .max(1)
+            .min(indices.len() - 1);
+
+        let (left, right) = indices.split_at_mut(split);
+
+        let left_node = Self::build_range(prims, left);
+        let right_node = Self::build_range(prims, right);
+
+        BVH::Node(Box::new(left_node), Box::new(right_node), bounds)
+    }
+
+    /// Evaluates `SAH_BUCKETS - 1` candidate splits of the (already
+    /// centroid-sorted) `indices` and returns the split position with the
+    /// lowest `area(left) * n_left + area(right) * n_right` cost.
+    fn sah_split(prims: &[Box<Prim+Send+Sync>], indices: &[usize], _axis: usize) -> Option<usize> {
+        let mut best_cost = ::std::f64::INFINITY;
+        let mut best_split = None;
+
+        for bucket in 1..SAH_BUCKETS {
+            let split = indices.len() * bucket / SAH_BUCKETS;
+            if split == 0 || split == indices.len() { continue }
+
+            let left_bounds = Self::bounds_of(prims, &indices[..split]);
+            let right_bounds = Self::bounds_of(prims, &indices[split..]);
+            let cost = left_bounds.area() * split as f64
+                + right_bounds.area() * (indices.len() - split) as f64;
+
+            if cost < best_cost {
+                best_cost = cost;
+                best_split = Some(split);
+            }
+        }
+
+        best_split
+    }
+
+    fn bounds_of(prims: &[Box<Prim+Send+Sync>], indices: &[usize]) -> AABB {
+        let mut bounds = AABB::empty();
+        for &i in indices.iter() {
+            bounds = bounds.union(&prims[i].aabb());
+        }
+        bounds
+    }
+
+    /// This node's bounding box, regardless of whether it's a `Node` or a
+    /// `Leaf`. Lets callers (e.g. `Mesh::aabb`) treat a whole `BVH` as
+    /// something with a single overall bound without matching on it.
+    pub fn bounds(&self) -> AABB {
+        match *self {
+            BVH::Node(_, _, bounds) => bounds,
+            BVH::Leaf(bounds, _) => bounds
+        }
+    }
+
+    /// Front-to-back traversal. `test(i, closest_t)` should check primitive
+    /// `i` against the ray and return its hit distance if it's closer than
+    /// `closest_t`, or `None` otherwise; the caller owns whatever hit
+    /// record/material lookup goes with that distance.
+    pub fn closest_hit<F>(&self, ray: &Ray, max_t: f64, test: &mut F) -> Option<(usize, f64)>
+        where F: FnMut(usize, f64) -> Option<f64>
+    {
+        match *self {
+            BVH::Leaf(ref bounds, ref candidates) => {
+                if bounds.intersects(ray, max_t).is_none() { return None }
+
+                let mut closest: Option<(usize, f64)> = None;
+                let mut closest_t = max_t;
+
+                for &i in candidates.iter() {
+                    if let Some(t) = test(i, closest_t) {
+                        closest_t = t;
+                        closest = Some((i, t));
+                    }
+                }
+
+                closest
+            },
+            BVH::Node(ref left, ref right, ref bounds) => {
+                if bounds.intersects(ray, max_t).is_none() { return None }
+
+                // Visit whichever child the ray enters first so a close hit
+                // narrows `max_t` before the farther child is ever tested;
+                // skip that child entirely once its own entry distance is no
+                // longer competitive.
+                let left_entry = left.bounds().intersects(ray, max_t);
+                let right_entry = right.bounds().intersects(ray, max_t);
+
+                let (near, far, far_entry) = match (left_entry, right_entry) {
+                    (Some(le), Some(re)) => {
+                        if re < le { (right, Some(left), Some(le)) } else { (left, Some(right), Some(re)) }
+                    },
+                    (Some(_), None) => (left, None, None),
+                    (None, Some(_)) => (right, None, None),
+                    (None, None) => return None
+                };
+
+                let near_hit = near.closest_hit(ray, max_t, test);
+                let narrowed_t = near_hit.map(|(_, t)| t).unwrap_or(max_t);
+
+                let far_hit = match far {
+                    Some(far_node) if far_entry.map_or(false, |t| t < narrowed_t) =>
+                        far_node.closest_hit(ray, narrowed_t, test),
+                    _ => None
+                };
+
+                match far_hit {
+                    Some(_) => far_hit,
+                    None => near_hit
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_aabb_intersects_hit() {
+    let bounds = AABB { min: Vec3 { x: -1.0, y: -1.0, z: -1.0 }, max: Vec3 { x: 1.0, y: 1.0, z: 1.0 } };
+    let ray = Ray { origin: Vec3 { x: 0.0, y: 0.0, z: -5.0 }, direction: Vec3 { x: 0.0, y: 0.0, z: 1.0 } };
+
+    let t = bounds.intersects(&ray, ::std::f64::INFINITY);
+    assert_eq!(t, Some(4.0));
+}
+
+#[test]
+fn test_aabb_intersects_miss() {
+    let bounds = AABB { min: Vec3 { x: -1.0, y: -1.0, z: -1.0 }, max: Vec3 { x: 1.0, y: 1.0, z: 1.0 } };
+    let ray = Ray { origin: Vec3 { x: 5.0, y: 5.0, z: -5.0 }, direction: Vec3 { x: 0.0, y: 0.0, z: 1.0 } };
+
+    assert_eq!(bounds.intersects(&ray, ::std::f64::INFINITY), None);
+}
+
+#[test]
+fn test_aabb_intersects_respects_max_t() {
+    let bounds = AABB { min: Vec3 { x: -1.0, y: -1.0, z: -1.0 }, max: Vec3 { x: 1.0, y: 1.0, z: 1.0 } };
+    let ray = Ray { origin: Vec3 { x: 0.0, y: 0.0, z: -5.0 }, direction: Vec3 { x: 0.0, y: 0.0, z: 1.0 } };
+
+    assert_eq!(bounds.intersects(&ray, 2.0), None);
+}
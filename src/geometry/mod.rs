@@ -0,0 +1,38 @@
+pub mod bvh;
+pub mod prims;
+
+use geometry::bvh::{AABB, BVH};
+use raytracer::Ray;
+
+/// Something a ray can hit: meshes, triangles, and anything else dropped
+/// into a scene's primitive list. `Send + Sync` so a `Vec<Box<Prim+Send+Sync>>`
+/// can be shared across render threads.
+pub trait Prim {
+    /// Distance along `ray` to the closest intersection nearer than `max_t`,
+    /// if any.
+    fn intersect(&self, ray: &Ray, max_t: f64) -> Option<f64>;
+
+    /// World-space bounding box; `bvh::BVH::build` uses this to bound and
+    /// partition primitives.
+    fn aabb(&self) -> AABB;
+}
+
+/// A triangle mesh, typically loaded via `util::import::from_obj`. `bvh`
+/// indexes `triangles` and is what `intersect` actually traverses, so a hit
+/// test doesn't have to walk every triangle linearly.
+pub struct Mesh {
+    pub triangles: Vec<Box<Prim+Send+Sync>>,
+    pub bvh: BVH
+}
+
+impl Prim for Mesh {
+    fn intersect(&self, ray: &Ray, max_t: f64) -> Option<f64> {
+        let triangles = &self.triangles;
+        let mut test = |i: usize, closest_t: f64| triangles[i].intersect(ray, closest_t);
+        self.bvh.closest_hit(ray, max_t, &mut test).map(|(_, t)| t)
+    }
+
+    fn aabb(&self) -> AABB {
+        self.bvh.bounds()
+    }
+}
@@ -0,0 +1,55 @@
+use geometry::bvh::AABB;
+use geometry::Prim;
+use material::Material;
+use raytracer::Ray;
+use util::sampling::cross;
+use vec3::Vec3;
+
+/// A triangle corner: interpolated position/normal/UV as parsed from an OBJ
+/// face. `Triangle` stores three of these rather than indexing back into
+/// the loader's vertex/normal/uv arrays, so it can outlive the parse.
+#[derive(Clone, Copy)]
+pub struct TriangleVertex {
+    pub pos: Vec3,
+    pub n: Vec3,
+    pub u: f64,
+    pub v: f64
+}
+
+pub struct Triangle {
+    pub v0: TriangleVertex,
+    pub v1: TriangleVertex,
+    pub v2: TriangleVertex,
+    pub material: Box<Material+Send+Sync>
+}
+
+impl Prim for Triangle {
+    /// Möller–Trumbore ray/triangle intersection.
+    fn intersect(&self, ray: &Ray, max_t: f64) -> Option<f64> {
+        let edge1 = self.v1.pos - self.v0.pos;
+        let edge2 = self.v2.pos - self.v0.pos;
+
+        let p = cross(ray.direction, edge2);
+        let det = edge1.dot(&p);
+        if det.abs() < 1e-12 { return None }
+
+        let inv_det = 1.0 / det;
+        let t_vec = ray.origin - self.v0.pos;
+        let u = t_vec.dot(&p) * inv_det;
+        if u < 0.0 || u > 1.0 { return None }
+
+        let q = cross(t_vec, edge1);
+        let v = ray.direction.dot(&q) * inv_det;
+        if v < 0.0 || u + v > 1.0 { return None }
+
+        let t = edge2.dot(&q) * inv_det;
+        if t > 1e-9 && t < max_t { Some(t) } else { None }
+    }
+
+    fn aabb(&self) -> AABB {
+        let mut bounds = AABB::from_point(self.v0.pos);
+        bounds.extend(self.v1.pos);
+        bounds.extend(self.v2.pos);
+        bounds
+    }
+}
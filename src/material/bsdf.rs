@@ -0,0 +1,27 @@
+use util::noise::Perlin;
+use vec3::Vec3;
+use rand::Rng;
+
+/// Generates and evaluates outgoing directions for a material, complementing
+/// `Material::sample`, which only evaluates a BRDF for an already-known
+/// light direction. A path tracer needs to *generate* `wi`, which `Material`
+/// alone can't do.
+pub trait BSDF {
+    /// Importance-samples an outgoing direction `wi` given the world-space
+    /// hit `point`, the geometric surface normal `n` and the direction back
+    /// towards the viewer/previous bounce `wo`. `noise` is the shared
+    /// `Perlin` field used to resolve any `ColorSource::Turbulence` inputs
+    /// (diffuse/roughness/bump) at `point`. Returns `(wi, throughput, pdf)`,
+    /// where `throughput` already folds in the BRDF and the
+    /// `cos(theta) / pdf` term, so the caller only has to multiply it into
+    /// the path's running throughput.
+    ///
+    /// Generic over `R: Rng` rather than taking `&mut Rng` since `Rng::gen`
+    /// is itself generic and so isn't available through a trait object.
+    fn sample_direction<R: Rng>(&self, point: Vec3, n: Vec3, wo: Vec3, noise: &Perlin, rng: &mut R) -> (Vec3, Vec3, f64);
+
+    /// The combined probability density of sampling `wi` via
+    /// `sample_direction`, for multiple-importance sampling against other
+    /// sampling strategies (e.g. light sampling).
+    fn pdf(&self, point: Vec3, n: Vec3, wo: Vec3, wi: Vec3, noise: &Perlin) -> f64;
+}
@@ -0,0 +1,65 @@
+use std::ops::{Index, IndexMut};
+use std::slice::IterMut;
+
+/// A straight-alpha RGBA pixel, one channel per byte.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ColorRGBA {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8
+}
+
+impl ColorRGBA {
+    pub fn new_rgb(r: u8, g: u8, b: u8) -> ColorRGBA {
+        ColorRGBA { r: r, g: g, b: b, a: 255 }
+    }
+
+    pub fn new_rgba(r: u8, g: u8, b: u8, a: u8) -> ColorRGBA {
+        ColorRGBA { r: r, g: g, b: b, a: a }
+    }
+
+    pub fn transparent() -> ColorRGBA {
+        ColorRGBA { r: 0, g: 0, b: 0, a: 0 }
+    }
+}
+
+/// A 2D grid of `ColorRGBA` pixels in row-major order: the render target for
+/// `util::import::from_image` and the input/output of `raytracer::filters`.
+pub struct Surface {
+    width: usize,
+    height: usize,
+    pixels: Vec<ColorRGBA>
+}
+
+impl Surface {
+    pub fn new(width: usize, height: usize, fill: ColorRGBA) -> Surface {
+        Surface { width: width, height: height, pixels: vec![fill; width * height] }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn iter_pixels_mut(&mut self) -> IterMut<ColorRGBA> {
+        self.pixels.iter_mut()
+    }
+}
+
+impl Index<(usize, usize)> for Surface {
+    type Output = ColorRGBA;
+
+    fn index(&self, (x, y): (usize, usize)) -> &ColorRGBA {
+        &self.pixels[y * self.width + x]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Surface {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut ColorRGBA {
+        &mut self.pixels[y * self.width + x]
+    }
+}
@@ -0,0 +1,253 @@
+use raytracer::compositor::{Surface, ColorRGBA};
+
+/// Image-space post-processing that consumes a `Surface` and produces a new
+/// one. Each filter except `color_matrix` (which transforms straight color)
+/// works on premultiplied alpha internally so the transparent pixels
+/// `from_image`/`ColorRGBA::transparent()` produce composite correctly
+/// instead of bleeding unassociated color at the edges.
+#[derive(Clone, Copy)]
+struct PremultipliedPixel {
+    r: f64,
+    g: f64,
+    b: f64,
+    a: f64
+}
+
+impl PremultipliedPixel {
+    fn from_color(c: ColorRGBA) -> PremultipliedPixel {
+        let alpha = c.a as f64 / 255.0;
+        PremultipliedPixel {
+            r: c.r as f64 * alpha,
+            g: c.g as f64 * alpha,
+            b: c.b as f64 * alpha,
+            a: c.a as f64
+        }
+    }
+
+    fn to_color(self) -> ColorRGBA {
+        let alpha = self.a / 255.0;
+        if alpha <= 0.0 {
+            ColorRGBA::new_rgba(0, 0, 0, 0)
+        } else {
+            ColorRGBA::new_rgba(
+                clamp_channel(self.r / alpha),
+                clamp_channel(self.g / alpha),
+                clamp_channel(self.b / alpha),
+                clamp_channel(self.a)
+            )
+        }
+    }
+
+    fn scale(self, s: f64) -> PremultipliedPixel {
+        PremultipliedPixel { r: self.r * s, g: self.g * s, b: self.b * s, a: self.a * s }
+    }
+
+    fn add(self, other: PremultipliedPixel) -> PremultipliedPixel {
+        PremultipliedPixel { r: self.r + other.r, g: self.g + other.g, b: self.b + other.b, a: self.a + other.a }
+    }
+
+    fn min(self, other: PremultipliedPixel) -> PremultipliedPixel {
+        PremultipliedPixel { r: self.r.min(other.r), g: self.g.min(other.g), b: self.b.min(other.b), a: self.a.min(other.a) }
+    }
+
+    fn max(self, other: PremultipliedPixel) -> PremultipliedPixel {
+        PremultipliedPixel { r: self.r.max(other.r), g: self.g.max(other.g), b: self.b.max(other.b), a: self.a.max(other.a) }
+    }
+}
+
+fn clamp_channel(v: f64) -> u8 {
+    if v < 0.0 { 0 } else if v > 255.0 { 255 } else { v.round() as u8 }
+}
+
+fn clamp_index(i: isize, len: usize) -> usize {
+    if i < 0 { 0 } else if i as usize >= len { len - 1 } else { i as usize }
+}
+
+/// Builds a normalized 1D Gaussian kernel wide enough to cover `sigma`.
+fn gaussian_kernel(sigma: f64) -> Vec<f64> {
+    let radius = (sigma * 3.0).ceil().max(1.0) as isize;
+    let mut kernel = Vec::with_capacity((2 * radius + 1) as usize);
+    let mut sum = 0.0;
+
+    for i in -radius..radius + 1 {
+        let x = i as f64;
+        let weight = (-(x * x) / (2.0 * sigma * sigma)).exp();
+        kernel.push(weight);
+        sum += weight;
+    }
+
+    for w in kernel.iter_mut() { *w /= sum; }
+    kernel
+}
+
+/// Separable Gaussian blur: convolves the 1D kernel horizontally, then
+/// vertically, clamping sample coordinates at the surface edges.
+pub fn gaussian_blur(surface: &Surface, sigma: f64) -> Surface {
+    let kernel = gaussian_kernel(sigma);
+    let radius = (kernel.len() / 2) as isize;
+    let width = surface.width();
+    let height = surface.height();
+
+    let mut horizontal = Surface::new(width, height, ColorRGBA::transparent());
+    for y in 0..height {
+        for x in 0..width {
+            let mut accum = PremultipliedPixel { r: 0.0, g: 0.0, b: 0.0, a: 0.0 };
+            for (k, &weight) in kernel.iter().enumerate() {
+                let sx = clamp_index(x as isize + k as isize - radius, width);
+                accum = accum.add(PremultipliedPixel::from_color(surface[(sx, y)]).scale(weight));
+            }
+            horizontal[(x, y)] = accum.to_color();
+        }
+    }
+
+    let mut result = Surface::new(width, height, ColorRGBA::transparent());
+    for y in 0..height {
+        for x in 0..width {
+            let mut accum = PremultipliedPixel { r: 0.0, g: 0.0, b: 0.0, a: 0.0 };
+            for (k, &weight) in kernel.iter().enumerate() {
+                let sy = clamp_index(y as isize + k as isize - radius, height);
+                accum = accum.add(PremultipliedPixel::from_color(horizontal[(x, sy)]).scale(weight));
+            }
+            result[(x, y)] = accum.to_color();
+        }
+    }
+
+    result
+}
+
+/// Convolves `surface` with an arbitrary `size x size` kernel (row-major),
+/// dividing the accumulated sum by `divisor` and adding `bias` afterwards.
+/// Useful for sharpen/edge-detect kernels that `gaussian_blur` doesn't cover.
+pub fn convolve_matrix(surface: &Surface, kernel: &[f64], size: usize, divisor: f64, bias: f64) -> Surface {
+    assert_eq!(kernel.len(), size * size);
+    let radius = (size / 2) as isize;
+    let width = surface.width();
+    let height = surface.height();
+
+    let mut result = Surface::new(width, height, ColorRGBA::transparent());
+    for y in 0..height {
+        for x in 0..width {
+            let mut accum = PremultipliedPixel { r: 0.0, g: 0.0, b: 0.0, a: 0.0 };
+            for ky in 0..size {
+                for kx in 0..size {
+                    let weight = kernel[ky * size + kx];
+                    let sx = clamp_index(x as isize + kx as isize - radius, width);
+                    let sy = clamp_index(y as isize + ky as isize - radius, height);
+                    accum = accum.add(PremultipliedPixel::from_color(surface[(sx, sy)]).scale(weight));
+                }
+            }
+
+            let result_pixel = PremultipliedPixel {
+                r: accum.r / divisor + bias,
+                g: accum.g / divisor + bias,
+                b: accum.b / divisor + bias,
+                a: accum.a / divisor
+            };
+            result[(x, y)] = result_pixel.to_color();
+        }
+    }
+
+    result
+}
+
+/// Applies a 4x5 affine transform (rows: R, G, B, A; columns: R, G, B, A,
+/// offset) to straight (non-premultiplied) color, each channel normalized to
+/// `[0, 1]`. Covers saturation, hue rotation and grayscale.
+pub fn color_matrix(surface: &Surface, matrix: &[[f64; 5]; 4]) -> Surface {
+    let width = surface.width();
+    let height = surface.height();
+    let mut result = Surface::new(width, height, ColorRGBA::transparent());
+
+    for y in 0..height {
+        for x in 0..width {
+            let c = surface[(x, y)];
+            let input = [c.r as f64 / 255.0, c.g as f64 / 255.0, c.b as f64 / 255.0, c.a as f64 / 255.0];
+            let mut output = [0.0f64; 4];
+
+            for row in 0..4 {
+                output[row] = matrix[row][0] * input[0]
+                    + matrix[row][1] * input[1]
+                    + matrix[row][2] * input[2]
+                    + matrix[row][3] * input[3]
+                    + matrix[row][4];
+            }
+
+            result[(x, y)] = ColorRGBA::new_rgba(
+                clamp_channel(output[0] * 255.0),
+                clamp_channel(output[1] * 255.0),
+                clamp_channel(output[2] * 255.0),
+                clamp_channel(output[3] * 255.0)
+            );
+        }
+    }
+
+    result
+}
+
+/// A `color_matrix` that desaturates to greyscale using Rec. 709 luma weights.
+pub fn grayscale_matrix() -> [[f64; 5]; 4] {
+    [
+        [0.2126, 0.7152, 0.0722, 0.0, 0.0],
+        [0.2126, 0.7152, 0.0722, 0.0, 0.0],
+        [0.2126, 0.7152, 0.0722, 0.0, 0.0],
+        [0.0,    0.0,    0.0,    1.0, 0.0]
+    ]
+}
+
+/// A `color_matrix` that scales saturation; `1.0` is identity, `0.0` matches
+/// `grayscale_matrix`.
+pub fn saturation_matrix(saturation: f64) -> [[f64; 5]; 4] {
+    let lr = 0.2126 * (1.0 - saturation);
+    let lg = 0.7152 * (1.0 - saturation);
+    let lb = 0.0722 * (1.0 - saturation);
+
+    [
+        [lr + saturation, lg,              lb,              0.0, 0.0],
+        [lr,              lg + saturation, lb,              0.0, 0.0],
+        [lr,              lg,              lb + saturation, 0.0, 0.0],
+        [0.0,             0.0,             0.0,              1.0, 0.0]
+    ]
+}
+
+#[derive(Clone, Copy)]
+pub enum MorphOp {
+    Erode,
+    Dilate
+}
+
+/// Erodes or dilates `surface` over a square window of the given `radius`,
+/// taking the componentwise min (erode) or max (dilate) of every pixel in
+/// the window in premultiplied space.
+pub fn morphology(surface: &Surface, radius: usize, op: MorphOp) -> Surface {
+    let width = surface.width();
+    let height = surface.height();
+    let r = radius as isize;
+
+    let mut result = Surface::new(width, height, ColorRGBA::transparent());
+
+    for y in 0..height {
+        for x in 0..width {
+            let mut window: Option<PremultipliedPixel> = None;
+
+            for dy in -r..r + 1 {
+                for dx in -r..r + 1 {
+                    let sx = clamp_index(x as isize + dx, width);
+                    let sy = clamp_index(y as isize + dy, height);
+                    let candidate = PremultipliedPixel::from_color(surface[(sx, sy)]);
+
+                    window = Some(match window {
+                        None => candidate,
+                        Some(current) => match op {
+                            MorphOp::Erode => current.min(candidate),
+                            MorphOp::Dilate => current.max(candidate)
+                        }
+                    });
+                }
+            }
+
+            result[(x, y)] = window.unwrap().to_color();
+        }
+    }
+
+    result
+}
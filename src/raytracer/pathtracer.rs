@@ -0,0 +1,143 @@
+use material::bsdf::BSDF;
+use material::materials::CookTorranceMaterial;
+use raytracer::Ray;
+use util::noise::Perlin;
+use vec3::Vec3;
+use rand::Rng;
+
+/// Bounce depth before Russian-roulette termination starts evaluating.
+const MIN_BOUNCES: u32 = 3;
+/// Hard cap so a sealed mirror box can't recurse forever.
+const MAX_BOUNCES: u32 = 64;
+
+/// What the integrator needs from a scene intersection: the hit point, its
+/// surface normal, and the material there. Kept concrete to
+/// `CookTorranceMaterial` rather than `Box<Material>` for the same reason
+/// `util::import` is: see the doc comment on `util::import::from_obj`.
+pub struct PathHit {
+    pub point: Vec3,
+    pub normal: Vec3,
+    pub material: CookTorranceMaterial
+}
+
+/// Estimates incident radiance along `ray` via unidirectional Monte Carlo
+/// path tracing: at each hit, importance-sample a bounce direction via the
+/// hit material's `BSDF` and accumulate `emission + weight * radiance(bounce)`,
+/// where `weight` already folds in `f(wi) * cos(theta) / pdf`. Russian
+/// roulette keeps the estimator unbiased once `depth` passes `MIN_BOUNCES`.
+///
+/// `intersect` is the scene's existing ray-intersection entry point; it's
+/// taken as a closure so this integrator doesn't need to know the concrete
+/// scene/primitive-list type. `noise` is the shared `Perlin` field passed
+/// down to the hit material's `BSDF`, so any `Turbulence`-driven
+/// diffuse/roughness/bump actually reaches the shading.
+pub fn radiance<F, R>(ray: Ray, intersect: &F, noise: &Perlin, rng: &mut R, depth: u32) -> Vec3
+    where F: Fn(&Ray) -> Option<PathHit>, R: Rng
+{
+    let hit = match intersect(&ray) {
+        Some(hit) => hit,
+        None => return zero()
+    };
+
+    let emission = hit.material.emission;
+
+    if depth > MAX_BOUNCES {
+        return emission;
+    }
+
+    let wo = ray.direction.scale(-1.0);
+    let (wi, mut weight, pdf) = hit.material.sample_direction(hit.point, hit.normal, wo, noise, rng);
+    if pdf <= 0.0 {
+        return emission;
+    }
+
+    if depth >= MIN_BOUNCES {
+        let survival = max_channel(weight).max(0.05).min(1.0);
+        if rng.gen::<f64>() > survival {
+            return emission;
+        }
+        weight = weight.scale(1.0 / survival);
+    }
+
+    let bounced = Ray { origin: hit.point + hit.normal.scale(1e-4), direction: wi };
+    let indirect = radiance(bounced, intersect, noise, rng, depth + 1);
+
+    emission + mul(weight, indirect)
+}
+
+/// Averages `samples_per_pixel` independent path-traced estimates for a
+/// single pixel, discarding any sample a degenerate bounce turned into NaN.
+pub fn sample_pixel<F, R>(ray: Ray, intersect: &F, noise: &Perlin, rng: &mut R, samples_per_pixel: u32) -> Vec3
+    where F: Fn(&Ray) -> Option<PathHit>, R: Rng
+{
+    let mut total = zero();
+    let mut accepted = 0u32;
+
+    for _ in 0..samples_per_pixel {
+        let sample = radiance(ray, intersect, noise, rng, 0);
+        if is_finite(sample) {
+            total = total + sample;
+            accepted += 1;
+        }
+    }
+
+    if accepted > 0 { total.scale(1.0 / accepted as f64) } else { zero() }
+}
+
+/// Accumulates per-pixel radiance across progressive passes so a render can
+/// keep refining an image instead of committing to one fixed sample count.
+pub struct ProgressiveBuffer {
+    width: usize,
+    height: usize,
+    accumulated: Vec<Vec3>,
+    passes: u32
+}
+
+impl ProgressiveBuffer {
+    pub fn new(width: usize, height: usize) -> ProgressiveBuffer {
+        ProgressiveBuffer {
+            width: width,
+            height: height,
+            accumulated: vec![zero(); width * height],
+            passes: 0
+        }
+    }
+
+    /// Renders one more sample per pixel via `ray_for_pixel` and folds it
+    /// into the running average.
+    pub fn add_pass<P, F, R>(&mut self, ray_for_pixel: &P, intersect: &F, noise: &Perlin, rng: &mut R)
+        where P: Fn(usize, usize) -> Ray, F: Fn(&Ray) -> Option<PathHit>, R: Rng
+    {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let sample = radiance(ray_for_pixel(x, y), intersect, noise, rng, 0);
+                if is_finite(sample) {
+                    let idx = y * self.width + x;
+                    self.accumulated[idx] = self.accumulated[idx] + sample;
+                }
+            }
+        }
+        self.passes += 1;
+    }
+
+    pub fn average(&self, x: usize, y: usize) -> Vec3 {
+        if self.passes == 0 { return zero() }
+        self.accumulated[y * self.width + x].scale(1.0 / self.passes as f64)
+    }
+}
+
+fn mul(a: Vec3, b: Vec3) -> Vec3 {
+    Vec3 { x: a.x * b.x, y: a.y * b.y, z: a.z * b.z }
+}
+
+fn max_channel(v: Vec3) -> f64 {
+    v.x.max(v.y).max(v.z)
+}
+
+fn is_finite(v: Vec3) -> bool {
+    v.x.is_finite() && v.y.is_finite() && v.z.is_finite()
+}
+
+fn zero() -> Vec3 {
+    Vec3 { x: 0.0, y: 0.0, z: 0.0 }
+}
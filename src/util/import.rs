@@ -1,15 +1,21 @@
+use geometry::bvh::BVH;
 use geometry::prims::{Triangle, TriangleVertex};
 use geometry::{Mesh, Prim};
 use image::GenericImage;
 use material::materials::CookTorranceMaterial;
 use raytracer::compositor::{Surface, ColorRGBA};
+use std::collections::HashMap;
 use std::fs::File;
 use std::path::Path;
 use std::io::{BufRead, BufReader};
+use util::mtl;
 use vec3::Vec3;
 
 /// This is limited to only CookTorranceMaterials, as I couldn't get a Box<Material> to clone
 /// a new material for each triangle primitive in the object model.
+///
+/// `material` is used as-is for any face that appears before a `usemtl`, and as a
+/// fallback for any `usemtl` name that the companion `mtllib` doesn't define.
 #[allow(dead_code)]
 pub fn from_obj(material: CookTorranceMaterial /*Box<Material>*/,
                 flip_normals: bool, filename: &str)
@@ -19,6 +25,7 @@ pub fn from_obj(material: CookTorranceMaterial /*Box<Material>*/,
     let total_bytes = file.metadata().ok().expect("Couldn't load metadata").len();
 
     let file = BufReader::new(file);
+    let obj_dir = Path::new(filename).parent();
 
     let start_time = ::time::get_time();
     let print_every = 2048u32;
@@ -30,12 +37,28 @@ pub fn from_obj(material: CookTorranceMaterial /*Box<Material>*/,
     let mut triangles: Vec<Box<Prim+Send+Sync>> = Vec::new();
     let mut tex_coords: Vec<Vec<f64>> = Vec::new();
 
+    let mut library: HashMap<String, CookTorranceMaterial> = HashMap::new();
+    let mut active_material = material.clone();
+
     for line_iter in file.lines() {
         let line = line_iter.unwrap();
         let tokens: Vec<&str> = line[..].split_whitespace().collect();
         if tokens.len() == 0 { continue }
 
         match tokens[0] {
+            "mtllib" => {
+                let path = match obj_dir {
+                    Some(dir) => dir.join(tokens[1]),
+                    None => Path::new(tokens[1]).to_path_buf()
+                };
+                library = mtl::from_mtl(path.to_str().unwrap());
+            },
+            "usemtl" => {
+                active_material = match library.get(tokens[1]) {
+                    Some(found) => found.clone(),
+                    None => material.clone()
+                };
+            },
             "v" => {
                 vertices.push(Vec3 {
                     x: tokens[1].parse().unwrap(),
@@ -58,41 +81,53 @@ pub fn from_obj(material: CookTorranceMaterial /*Box<Material>*/,
                 });
             },
             "f" => {
-                // ["f", "1/2/3", "2/2/2", "12//4"] => [[1, 2, 3], [2, 2, 2], [12, -1u, 4]]
-                let pairs: Vec<Vec<usize>> = tokens.tail().iter().map( |token| {
+                // ["f", "1/2/3", "2/2/2", "12//4"] => [[1, 2, 3], [2, 2, 2], [12, !0, 4]]
+                // Faces may carry any number of vertices (quads, n-gons); fan-triangulate
+                // them below rather than assuming exactly three.
+                let pairs: Vec<[usize; 3]> = tokens[1..].iter().map( |token| {
                     let str_tokens: Vec<&str> = token.split('/').collect();
-                    str_tokens.iter().map( |str_tok| {
-                        match str_tok.parse::<usize>().ok() {
-                            Some(usize_tok) => usize_tok - 1,
-                            None => !0 // No data available/not supplied
-                        }
-                    }).collect()
+                    let mut indices = [!0usize; 3];
+                    for (i, str_tok) in str_tokens.iter().enumerate() {
+                        if str_tok.len() == 0 { continue } // No data available/not supplied
+                        let list_len = match i {
+                            0 => vertices.len(),
+                            1 => tex_coords.len(),
+                            _ => normals.len()
+                        };
+                        indices[i] = resolve_index(str_tok.parse().unwrap(), list_len);
+                    }
+                    indices
                 }).collect();
 
-                // If no texture coordinates were supplied, default to zero.
-                // We store nothing supplied as !0
-                let (u, v) = if pairs[0][1] != !0 {
-                    (vec![
-                        tex_coords[pairs[0][1]][0],
-                        tex_coords[pairs[1][1]][0],
-                        tex_coords[pairs[2][1]][0]
-                    ],
-                    vec![
-                        tex_coords[pairs[0][1]][1],
-                        tex_coords[pairs[1][1]][1],
-                        tex_coords[pairs[2][1]][1]
-                    ])
-                } else {
-                    (vec![0.0, 0.0, 0.0],
-                     vec![0.0, 0.0, 0.0])
-                };
-
-                triangles.push(Box::new(Triangle {
-                    v0: TriangleVertex { pos: vertices[pairs[0][0]], n: normals[pairs[0][2]], u: u[0], v: v[0] },
-                    v1: TriangleVertex { pos: vertices[pairs[1][0]], n: normals[pairs[1][2]], u: u[1], v: v[1] },
-                    v2: TriangleVertex { pos: vertices[pairs[2][0]], n: normals[pairs[2][2]], u: u[2], v: v[2] },
-                    material: Box::new(material.clone()),
-                }));
+                // Fan-triangulate: vertices (0, i, i+1) for i in 1..n-1.
+                for i in 1..pairs.len() - 1 {
+                    let face = [pairs[0], pairs[i], pairs[i + 1]];
+
+                    // If no texture coordinates were supplied, default to zero.
+                    // We store nothing supplied as !0
+                    let (u, v) = if face[0][1] != !0 {
+                        (vec![
+                            tex_coords[face[0][1]][0],
+                            tex_coords[face[1][1]][0],
+                            tex_coords[face[2][1]][0]
+                        ],
+                        vec![
+                            tex_coords[face[0][1]][1],
+                            tex_coords[face[1][1]][1],
+                            tex_coords[face[2][1]][1]
+                        ])
+                    } else {
+                        (vec![0.0, 0.0, 0.0],
+                         vec![0.0, 0.0, 0.0])
+                    };
+
+                    triangles.push(Box::new(Triangle {
+                        v0: TriangleVertex { pos: vertices[face[0][0]], n: normals[face[0][2]], u: u[0], v: v[0] },
+                        v1: TriangleVertex { pos: vertices[face[1][0]], n: normals[face[1][2]], u: u[1], v: v[1] },
+                        v2: TriangleVertex { pos: vertices[face[2][0]], n: normals[face[2][2]], u: u[2], v: v[2] },
+                        material: Box::new(active_material.clone()),
+                    }));
+                }
             },
             _ => {}
         }
@@ -107,11 +142,42 @@ pub fn from_obj(material: CookTorranceMaterial /*Box<Material>*/,
     // Cheat the progress meter
     ::util::print_progress("Bytes", start_time, total_bytes as usize, total_bytes as usize);
 
+    // Building the BVH is the last step so it sees the fully populated
+    // triangle list; Mesh::intersect traverses it instead of testing every
+    // triangle linearly.
+    let bvh = BVH::build(&triangles);
+
     Mesh {
-        triangles: triangles
+        triangles: triangles,
+        bvh: bvh
     }
 }
 
+/// Resolves a raw OBJ index reference into a 0-based index into the list it
+/// names. Positive references are 1-based; negative references are relative,
+/// with `-k` meaning "the `k`-th most recently defined element" at this point
+/// in the file.
+fn resolve_index(raw: i64, list_len: usize) -> usize {
+    if raw < 0 {
+        (list_len as i64 + raw) as usize
+    } else {
+        (raw - 1) as usize
+    }
+}
+
+#[test]
+fn test_resolve_index_positive_is_one_based() {
+    assert_eq!(resolve_index(1, 10), 0);
+    assert_eq!(resolve_index(10, 10), 9);
+}
+
+#[test]
+fn test_resolve_index_negative_is_relative_to_list_len() {
+    // -1 means "the last element defined so far".
+    assert_eq!(resolve_index(-1, 10), 9);
+    assert_eq!(resolve_index(-10, 10), 0);
+}
+
 pub fn from_image<P: AsRef<Path>>(path: P) -> Result<Surface, String> {
     let image = match ::image::open(path) {
         Ok(image) => image.to_rgba(),
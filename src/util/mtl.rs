@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use material::materials::CookTorranceMaterial;
+use vec3::Vec3;
+
+/// Parses a Wavefront `.mtl` material library into a lookup from material
+/// name (the argument to `newmtl`) to the `CookTorranceMaterial` it
+/// describes. Only the statements that map onto `CookTorranceMaterial`'s
+/// fields are honored; anything else (comments, texture maps, ...) is read
+/// but ignored.
+#[allow(dead_code)]
+pub fn from_mtl(filename: &str) -> HashMap<String, CookTorranceMaterial> {
+    let file = File::open(&filename).ok().expect("Couldn't open material library");
+    let file = BufReader::new(file);
+
+    let mut materials = HashMap::new();
+    let mut current_name: Option<String> = None;
+    let mut current = default_material();
+    let mut pending_dissolve: Option<f64> = None;
+
+    for line_iter in file.lines() {
+        let line = line_iter.unwrap();
+        let tokens: Vec<&str> = line[..].split_whitespace().collect();
+        if tokens.len() == 0 || tokens[0].starts_with("#") { continue }
+
+        match tokens[0] {
+            "newmtl" => {
+                if let Some(name) = current_name.take() {
+                    finish_material(&mut current, pending_dissolve);
+                    materials.insert(name, current);
+                }
+                current = default_material();
+                pending_dissolve = None;
+                current_name = Some(tokens[1].to_string());
+            },
+            "Ka" => current.ambient = parse_rgb(&tokens),
+            "Kd" => current.diffuse = parse_rgb(&tokens),
+            "Ks" => current.specular = parse_rgb(&tokens),
+            // Kept separate from `ambient`/`Ka`: `ambient` is reflected light,
+            // `emission` is self-emitted, and `raytracer::pathtracer` only
+            // treats the latter as a light source.
+            "Ke" => current.emission = current.emission + parse_rgb(&tokens),
+            "Ns" => {
+                let n_s: f64 = tokens[1].parse().unwrap();
+                // Remap Blinn-Phong shininess onto the microfacet roughness term.
+                current.roughness = 1.0 / n_s.max(1.0).sqrt();
+            },
+            "Ni" => current.ior = tokens[1].parse().unwrap(),
+            // `d`/`Tr` describe the transmissive color as `diffuse`, but MTL
+            // doesn't guarantee `Kd` precedes them; defer until the block
+            // ends in `finish_material` so `diffuse` has its final value.
+            "d"  => pending_dissolve = Some(tokens[1].parse().unwrap()),
+            "Tr" => pending_dissolve = Some(1.0 - tokens[1].parse::<f64>().unwrap()),
+            "illum" => {
+                let model: i32 = tokens[1].parse().unwrap();
+                if model >= 3 { current.k_sg = 1.0 }
+            },
+            _ => {} // No CookTorranceMaterial equivalent
+        }
+    }
+
+    if let Some(name) = current_name.take() {
+        finish_material(&mut current, pending_dissolve);
+        materials.insert(name, current);
+    }
+
+    materials
+}
+
+fn parse_rgb(tokens: &[&str]) -> Vec3 {
+    Vec3 {
+        x: tokens[1].parse().unwrap(),
+        y: tokens[2].parse().unwrap(),
+        z: tokens[3].parse().unwrap()
+    }
+}
+
+/// `d` (dissolve, 1.0 = fully opaque) and `Tr` (transparency, its inverse)
+/// both describe the same thing; fold whichever one the block saw into the
+/// transmissive terms so the material actually lets light through, now that
+/// `diffuse` holds its final parsed value.
+fn finish_material(material: &mut CookTorranceMaterial, dissolve: Option<f64>) {
+    if let Some(dissolve) = dissolve {
+        if dissolve < 1.0 {
+            material.k_tg = 1.0 - dissolve;
+            material.transmission = material.diffuse;
+        }
+    }
+}
+
+fn default_material() -> CookTorranceMaterial {
+    CookTorranceMaterial {
+        k_a: 1.0,
+        k_d: 1.0,
+        k_s: 1.0,
+        k_sg: 0.0,
+        k_tg: 0.0,
+        ambient: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+        diffuse: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+        transmission: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+        specular: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+        emission: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+        roughness: 1.0,
+        gauss_constant: 1.0,
+        ior: 1.0,
+        diffuse_source: None,
+        roughness_source: None,
+        normal_source: None
+    }
+}
@@ -0,0 +1,48 @@
+use rand::Rng;
+use vec3::Vec3;
+
+/// Builds an orthonormal basis (tangent, bitangent) perpendicular to `n`,
+/// used to map samples drawn in a local hemisphere frame into world space.
+pub fn orthonormal_basis(n: Vec3) -> (Vec3, Vec3) {
+    let up = if n.x.abs() > 0.9 {
+        Vec3 { x: 0.0, y: 1.0, z: 0.0 }
+    } else {
+        Vec3 { x: 1.0, y: 0.0, z: 0.0 }
+    };
+
+    let tangent = cross(up, n);
+    let tangent = tangent.scale(1.0 / tangent.dot(&tangent).sqrt());
+    let bitangent = cross(n, tangent);
+
+    (tangent, bitangent)
+}
+
+/// Draws a cosine-weighted direction over the hemisphere around `n`:
+/// `theta = acos(sqrt(1 - r1))`, `phi = 2*pi*r2`, mapped into world space
+/// through `orthonormal_basis`.
+pub fn cosine_sample_hemisphere<R: Rng>(n: Vec3, rng: &mut R) -> Vec3 {
+    let r1: f64 = rng.gen();
+    let r2: f64 = rng.gen();
+
+    let theta = (1.0 - r1).sqrt().acos();
+    let phi = 2.0 * ::std::f64::consts::PI * r2;
+    let (sin_theta, cos_theta) = theta.sin_cos();
+
+    let (tangent, bitangent) = orthonormal_basis(n);
+    let world = tangent.scale(sin_theta * phi.cos())
+        + bitangent.scale(sin_theta * phi.sin())
+        + n.scale(cos_theta);
+
+    // A degenerate sample would otherwise hand the caller a NaN direction
+    // and poison the whole pixel.
+    let len_sq = world.dot(&world);
+    if len_sq > 0.0 { world.scale(1.0 / len_sq.sqrt()) } else { n }
+}
+
+pub fn cross(a: Vec3, b: Vec3) -> Vec3 {
+    Vec3 {
+        x: a.y * b.z - a.z * b.y,
+        y: a.z * b.x - a.x * b.z,
+        z: a.x * b.y - a.y * b.x
+    }
+}